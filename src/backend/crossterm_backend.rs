@@ -0,0 +1,127 @@
+use std::io::{self, Stdout};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use crossterm::{
+    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::{
+    backend::CrosstermBackend,
+    terminal::{Terminal, TerminalOptions, Viewport},
+};
+
+use crate::game::Game;
+use crate::ui;
+
+use super::{GameBackend, Key};
+
+/// Tracks whether the live backend entered the alternate screen, so
+/// `emergency_restore` (which has no access to the backend instance) knows
+/// whether leaving it is safe.
+static ALTERNATE_SCREEN: AtomicBool = AtomicBool::new(false);
+
+pub struct CrosstermGameBackend {
+    terminal: Terminal<CrosstermBackend<Stdout>>,
+    inline: bool,
+}
+
+impl CrosstermGameBackend {
+    /// Full-screen backend: takes over the whole terminal via the alternate
+    /// screen buffer.
+    pub fn new() -> io::Result<Self> {
+        let terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+        Ok(CrosstermGameBackend {
+            terminal,
+            inline: false,
+        })
+    }
+
+    /// Inline backend: draws into a fixed-height viewport anchored at the
+    /// cursor, leaving the rest of the scrollback untouched.
+    pub fn new_inline(height: u16) -> io::Result<Self> {
+        let terminal = Terminal::with_options(
+            CrosstermBackend::new(io::stdout()),
+            TerminalOptions {
+                viewport: Viewport::Inline(height),
+            },
+        )?;
+        Ok(CrosstermGameBackend {
+            terminal,
+            inline: true,
+        })
+    }
+}
+
+impl GameBackend for CrosstermGameBackend {
+    fn setup(&mut self) -> io::Result<()> {
+        enable_raw_mode()?;
+        if !self.inline {
+            execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture)?;
+            ALTERNATE_SCREEN.store(true, Ordering::SeqCst);
+        }
+        Ok(())
+    }
+
+    fn teardown(&mut self) -> io::Result<()> {
+        restore_raw_and_screen()?;
+        self.terminal.show_cursor()
+    }
+
+    fn next_key(&mut self) -> io::Result<Key> {
+        loop {
+            if let Event::Key(key) = event::read()? {
+                return Ok(map_key(key.code));
+            }
+        }
+    }
+
+    fn poll_key(&mut self, timeout: Duration) -> io::Result<Option<Key>> {
+        if !event::poll(timeout)? {
+            return Ok(None);
+        }
+        if let Event::Key(key) = event::read()? {
+            return Ok(Some(map_key(key.code)));
+        }
+        Ok(None)
+    }
+
+    fn draw(&mut self, game: &mut Game) -> io::Result<()> {
+        let inline = self.inline;
+        self.terminal.draw(|f| ui::draw(f, game, inline))?;
+        Ok(())
+    }
+}
+
+/// Leaves raw mode and, if it was entered, the alternate screen. Shared by
+/// `GameBackend::teardown` and `emergency_restore` so the two can't drift out
+/// of sync with each other over `ALTERNATE_SCREEN`.
+fn restore_raw_and_screen() -> io::Result<()> {
+    disable_raw_mode()?;
+    if ALTERNATE_SCREEN.swap(false, Ordering::SeqCst) {
+        execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture)?;
+    }
+    Ok(())
+}
+
+fn map_key(code: KeyCode) -> Key {
+    match code {
+        KeyCode::Char(c) => Key::Char(c),
+        KeyCode::Backspace => Key::Backspace,
+        KeyCode::Enter => Key::Enter,
+        KeyCode::Esc => Key::Esc,
+        KeyCode::Left => Key::Left,
+        KeyCode::Right => Key::Right,
+        KeyCode::Up => Key::Up,
+        KeyCode::Down => Key::Down,
+        _ => Key::Other,
+    }
+}
+
+/// Restores the terminal outside of a `CrosstermGameBackend` instance, e.g.
+/// from a panic hook that can't reach the `Terminal` owned by `main`.
+pub fn emergency_restore() {
+    let _ = restore_raw_and_screen();
+    let _ = execute!(io::stdout(), crossterm::cursor::Show);
+}
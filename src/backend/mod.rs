@@ -0,0 +1,48 @@
+//! Abstracts terminal setup/teardown, input, and rendering behind a trait so
+//! the core game logic in `game`/`ui` never has to depend on a specific
+//! terminal crate. `crossterm` is the default backend; other ecosystems can
+//! be added behind their own feature flag (see `termion`).
+
+use std::io;
+use std::time::Duration;
+
+use crate::game::Game;
+
+mod crossterm_backend;
+pub use crossterm_backend::{emergency_restore, CrosstermGameBackend};
+
+#[cfg(feature = "termion")]
+mod termion_backend;
+#[cfg(feature = "termion")]
+pub use termion_backend::TermionGameBackend;
+
+/// A single input event, independent of the underlying terminal crate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Key {
+    Char(char),
+    Backspace,
+    Enter,
+    Esc,
+    Left,
+    Right,
+    Up,
+    Down,
+    Other,
+}
+
+/// Everything `main` needs from a terminal: bring it up, read keys from it,
+/// draw a frame to it, and put it back the way it found it.
+pub trait GameBackend {
+    fn setup(&mut self) -> io::Result<()>;
+    fn teardown(&mut self) -> io::Result<()>;
+    /// Blocks for the next keystroke. `main`'s event loop uses `poll_key`
+    /// instead so it can redraw on a tick, but this is kept as part of the
+    /// contract for a caller that's fine blocking.
+    #[allow(dead_code)]
+    fn next_key(&mut self) -> io::Result<Key>;
+    /// Waits up to `timeout` for a keystroke, returning `None` if none
+    /// arrived. Lets `main` redraw on a tick (e.g. a live timed-mode
+    /// countdown) even while the player isn't typing.
+    fn poll_key(&mut self, timeout: Duration) -> io::Result<Option<Key>>;
+    fn draw(&mut self, game: &mut Game) -> io::Result<()>;
+}
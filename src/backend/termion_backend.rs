@@ -0,0 +1,86 @@
+//! Alternative backend for terminals without `crossterm` support. Enabled via
+//! the `termion` Cargo feature; not built by default.
+
+use std::io::{self, Stdout, Write};
+use std::time::{Duration, Instant};
+
+use ratatui::{backend::TermionBackend, Terminal};
+use termion::event::Key as TermionKey;
+use termion::input::{MouseTerminal, TermRead};
+use termion::raw::{IntoRawMode, RawTerminal};
+use termion::screen::{AlternateScreen, IntoAlternateScreen};
+
+use crate::game::Game;
+use crate::ui;
+
+use super::{GameBackend, Key};
+
+type Backend = TermionBackend<AlternateScreen<MouseTerminal<RawTerminal<Stdout>>>>;
+
+pub struct TermionGameBackend {
+    terminal: Terminal<Backend>,
+}
+
+impl TermionGameBackend {
+    pub fn new() -> io::Result<Self> {
+        let raw = io::stdout().into_raw_mode()?;
+        let mouse = MouseTerminal::from(raw);
+        let screen = mouse.into_alternate_screen()?;
+        let terminal = Terminal::new(TermionBackend::new(screen))?;
+        Ok(TermionGameBackend { terminal })
+    }
+}
+
+impl GameBackend for TermionGameBackend {
+    fn setup(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn teardown(&mut self) -> io::Result<()> {
+        self.terminal.show_cursor()?;
+        self.terminal.backend_mut().flush()
+    }
+
+    fn next_key(&mut self) -> io::Result<Key> {
+        let mut keys = io::stdin().keys();
+        loop {
+            if let Some(key) = keys.next() {
+                return Ok(map_key(key?));
+            }
+        }
+    }
+
+    fn poll_key(&mut self, timeout: Duration) -> io::Result<Option<Key>> {
+        // termion has no native event queue with a timeout, so poll the
+        // non-blocking stdin stream in short steps until one arrives or the
+        // budget runs out.
+        let deadline = Instant::now() + timeout;
+        let mut keys = termion::async_stdin().keys();
+        while Instant::now() < deadline {
+            if let Some(key) = keys.next() {
+                return Ok(Some(map_key(key?)));
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        Ok(None)
+    }
+
+    fn draw(&mut self, game: &mut Game) -> io::Result<()> {
+        self.terminal.draw(|f| ui::draw(f, game, false))?;
+        Ok(())
+    }
+}
+
+fn map_key(key: TermionKey) -> Key {
+    match key {
+        TermionKey::Char('\n') => Key::Enter,
+        TermionKey::Char(c) => Key::Char(c),
+        TermionKey::Backspace => Key::Backspace,
+        TermionKey::Esc => Key::Esc,
+        TermionKey::Left => Key::Left,
+        TermionKey::Right => Key::Right,
+        TermionKey::Up => Key::Up,
+        TermionKey::Down => Key::Down,
+        _ => Key::Other,
+    }
+}
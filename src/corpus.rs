@@ -0,0 +1,297 @@
+//! Pluggable sources for a round's target text: pick where the words come
+//! from (`PromptSource`) and how much of it to generate (`PromptLength`).
+//!
+//! `PromptSource` is an enum of backends rather than a `dyn Trait`, since the
+//! Waiting screen cycles through `SELECTABLE_SOURCES` by equality (see
+//! `Game::cycle_source`) — something a boxed trait object can't give us for
+//! free. New backends (like `FrequencyList` below) are added as variants.
+
+use std::fmt;
+use std::fs;
+use std::io;
+
+use lipsum::lipsum;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// How much text a generated prompt should contain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PromptLength {
+    Words(usize),
+    Chars(usize),
+    /// Seconds the round is expected to run for; generates a generous word
+    /// budget so the prompt doesn't run out before the timer does.
+    Timed(u64),
+}
+
+impl Default for PromptLength {
+    fn default() -> Self {
+        PromptLength::Words(5)
+    }
+}
+
+/// Where the words that make up a prompt come from.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum PromptSource {
+    #[default]
+    Lipsum,
+    CommonWords,
+    Grammar,
+    /// Only reachable by constructing a `PromptSource` directly (e.g. from a
+    /// future CLI flag), since the UI doesn't prompt for a path yet.
+    #[allow(dead_code)]
+    File(String),
+    /// A `word<whitespace>count` frequency list; words are sampled
+    /// proportionally to their counts, so common words recur more often.
+    /// See `parse_frequency_list`. Like `File`, only reachable by
+    /// constructing a `PromptSource` directly for now.
+    #[allow(dead_code)]
+    FrequencyList(String),
+}
+
+impl PromptSource {
+    pub fn label(&self) -> String {
+        match self {
+            PromptSource::Lipsum => "lipsum".to_string(),
+            PromptSource::CommonWords => "common words".to_string(),
+            PromptSource::Grammar => "grammar".to_string(),
+            PromptSource::File(path) => format!("file ({path})"),
+            PromptSource::FrequencyList(path) => format!("frequency list ({path})"),
+        }
+    }
+}
+
+/// The sources a player can cycle through from the Waiting screen. `File`
+/// and `FrequencyList` are only reachable by constructing a `PromptSource`
+/// directly (e.g. from a future CLI flag), since they need a path the UI
+/// doesn't prompt for yet.
+pub const SELECTABLE_SOURCES: &[PromptSource] =
+    &[PromptSource::Lipsum, PromptSource::CommonWords, PromptSource::Grammar];
+
+/// Presets a player can step through with a single control, spanning all
+/// three length modes.
+pub const LENGTH_PRESETS: &[PromptLength] = &[
+    PromptLength::Words(10),
+    PromptLength::Words(25),
+    PromptLength::Words(50),
+    PromptLength::Chars(100),
+    PromptLength::Chars(250),
+    PromptLength::Timed(15),
+    PromptLength::Timed(30),
+    PromptLength::Timed(60),
+];
+
+const COMMON_WORDS: &[&str] = &[
+    "the", "of", "and", "a", "to", "in", "is", "you", "that", "it", "he", "was", "for", "on",
+    "are", "as", "with", "his", "they", "at", "be", "this", "have", "from", "or", "one", "had",
+    "by", "word", "but", "not", "what", "all", "were", "we", "when", "your", "can", "said",
+    "there", "use", "an", "each", "which", "she", "do", "how", "their", "if", "will",
+];
+
+const ADJECTIVES: &[&str] = &[
+    "quick", "quiet", "bright", "old", "tiny", "bold", "lazy", "sharp", "clever", "calm",
+];
+const NOUNS: &[&str] = &[
+    "fox", "engine", "river", "table", "signal", "garden", "rocket", "forest", "ocean", "lantern",
+];
+const VERBS: &[&str] = &[
+    "jumps", "builds", "hides", "runs", "writes", "waits", "grows", "sings", "drifts", "climbs",
+];
+
+const TEMPLATES: &[&[&str]] = &[
+    &["the", "{adjective}", "{noun}", "{verb}"],
+    &["a", "{adjective}", "{noun}", "{verb}", "quietly"],
+    &["the", "{noun}", "{verb}", "the", "{adjective}", "{noun}"],
+];
+
+fn expand_template(template: &[&str], rng: &mut impl Rng) -> String {
+    template
+        .iter()
+        .map(|token| match *token {
+            "{adjective}" => *ADJECTIVES.choose(rng).unwrap(),
+            "{noun}" => *NOUNS.choose(rng).unwrap(),
+            "{verb}" => *VERBS.choose(rng).unwrap(),
+            literal => literal,
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// A malformed line in a `FrequencyList` file.
+#[derive(Debug)]
+pub struct WordListError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for WordListError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for WordListError {}
+
+/// Parses a `word<whitespace>count` frequency list, one entry per line.
+/// Hand-rolled line/field scanning rather than a parser-combinator
+/// dependency: blank lines are skipped, and a malformed line fails with its
+/// line number and a description of what was wrong with it.
+fn parse_frequency_list(contents: &str) -> Result<Vec<(String, u32)>, WordListError> {
+    let mut entries = Vec::new();
+    for (index, line) in contents.lines().enumerate() {
+        let line_no = index + 1;
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        let mut fields = trimmed.split_whitespace();
+        let word = fields.next().ok_or_else(|| WordListError {
+            line: line_no,
+            message: "expected a word followed by a count".to_string(),
+        })?;
+        let count_str = fields.next().ok_or_else(|| WordListError {
+            line: line_no,
+            message: format!("missing count after word {word:?}"),
+        })?;
+        if fields.next().is_some() {
+            return Err(WordListError {
+                line: line_no,
+                message: "expected exactly two fields: word and count".to_string(),
+            });
+        }
+        let count: u32 = count_str.parse().map_err(|_| WordListError {
+            line: line_no,
+            message: format!("count {count_str:?} is not a non-negative integer"),
+        })?;
+
+        entries.push((word.to_string(), count));
+    }
+    Ok(entries)
+}
+
+/// Samples `n` words from a parsed frequency list proportionally to their
+/// counts, so common words come up more often than rare ones.
+fn words_from_frequency(pool: &[(String, u32)], n: usize, rng: &mut impl Rng) -> String {
+    (0..n)
+        .filter_map(|_| pool.choose_weighted(rng, |(_, count)| *count as f64).ok())
+        .map(|(word, _)| word.as_str())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Picks `n` words at random (with replacement) from `pool`, so short files
+/// can still fill a long prompt. Returns an empty string for an empty pool.
+fn words_from_pool(pool: &[String], n: usize, rng: &mut impl Rng) -> String {
+    if pool.is_empty() {
+        return String::new();
+    }
+    (0..n)
+        .map(|_| pool.choose(rng).unwrap().as_str())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn words(source: &PromptSource, n: usize) -> io::Result<String> {
+    let mut rng = rand::thread_rng();
+    match source {
+        PromptSource::Lipsum => Ok(lipsum(n)),
+        PromptSource::CommonWords => {
+            let pool: Vec<String> = COMMON_WORDS.iter().map(|w| w.to_string()).collect();
+            Ok(words_from_pool(&pool, n, &mut rng))
+        }
+        PromptSource::Grammar => {
+            let mut phrase = String::new();
+            while phrase.split_whitespace().count() < n {
+                if !phrase.is_empty() {
+                    phrase.push(' ');
+                }
+                let template = TEMPLATES.choose(&mut rng).unwrap();
+                phrase.push_str(&expand_template(template, &mut rng));
+            }
+            Ok(phrase.split_whitespace().take(n).collect::<Vec<_>>().join(" "))
+        }
+        PromptSource::File(path) => {
+            let contents = fs::read_to_string(path)?;
+            let pool: Vec<String> = contents.split_whitespace().map(|w| w.to_string()).collect();
+            Ok(words_from_pool(&pool, n, &mut rng))
+        }
+        PromptSource::FrequencyList(path) => {
+            let contents = fs::read_to_string(path)?;
+            let pool = parse_frequency_list(&contents)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))?;
+            Ok(words_from_frequency(&pool, n, &mut rng))
+        }
+    }
+}
+
+/// Keeps generating words from `source` until the text is at least `n`
+/// characters, then trims to the nearest preceding word boundary.
+fn chars(source: &PromptSource, n: usize) -> io::Result<String> {
+    let mut text = String::new();
+    while text.chars().count() < n {
+        let chunk = words(source, 10)?;
+        if chunk.is_empty() {
+            // Source has nothing left to give (e.g. an empty corpus file);
+            // stop growing rather than spin forever.
+            break;
+        }
+        text.push(' ');
+        text.push_str(&chunk);
+    }
+    let trimmed: String = text.chars().take(n).collect();
+    match trimmed.rfind(' ') {
+        Some(boundary) if boundary > 0 => Ok(trimmed[..boundary].trim().to_string()),
+        _ => Ok(trimmed.trim().to_string()),
+    }
+}
+
+/// Generates a round's target text from the given source and length.
+pub fn generate(source: &PromptSource, length: PromptLength) -> io::Result<String> {
+    match length {
+        PromptLength::Words(n) => words(source, n),
+        PromptLength::Chars(n) => chars(source, n),
+        // Typing speeds rarely exceed ~150wpm; a few words per second of
+        // runway is generous enough that the prompt never runs dry.
+        PromptLength::Timed(secs) => words(source, (secs as usize * 3).max(20)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_word_and_count_pairs() {
+        let entries = parse_frequency_list("the 120\napple 7\n").unwrap();
+        assert_eq!(
+            entries,
+            vec![("the".to_string(), 120), ("apple".to_string(), 7)]
+        );
+    }
+
+    #[test]
+    fn skips_blank_lines() {
+        let entries = parse_frequency_list("the 120\n\n  \napple 7\n").unwrap();
+        assert_eq!(entries.len(), 2);
+    }
+
+    #[test]
+    fn reports_line_number_for_a_missing_count() {
+        let err = parse_frequency_list("the 120\napple\n").unwrap_err();
+        assert_eq!(err.line, 2);
+    }
+
+    #[test]
+    fn reports_line_number_for_an_extra_field() {
+        let err = parse_frequency_list("the 120 extra\n").unwrap_err();
+        assert_eq!(err.line, 1);
+    }
+
+    #[test]
+    fn reports_line_number_for_a_non_numeric_count() {
+        let err = parse_frequency_list("the 120\napple many\n").unwrap_err();
+        assert_eq!(err.line, 2);
+        assert!(err.message.contains("many"));
+    }
+}
@@ -1,11 +1,32 @@
-use crossterm::event::{KeyCode, KeyEvent};
-use lipsum::lipsum;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::backend::Key;
+use crate::corpus::{self, PromptLength, PromptSource, LENGTH_PRESETS, SELECTABLE_SOURCES};
+use crate::mods::GameMods;
+use crate::replay::{self, Replay, ReplayEvent};
+use crate::scheduler::Scheduler;
+use crate::storage::{RoundRecord, Storage};
+
+/// One unit of prompt text. Stored as a grapheme cluster (not a `char`) so
+/// accented letters, emoji, and other multi-codepoint glyphs stay single
+/// units to type and render.
 #[derive(Clone)]
 pub struct GameChar {
-    expected_char: char,
-    given_char: Option<char>,
+    expected_char: String,
+    given_char: Option<String>,
+}
+
+impl GameChar {
+    /// Terminal columns this grapheme should occupy, so the renderer can pad
+    /// a narrower substitute (e.g. the typed overlay) to keep alignment.
+    pub fn display_width(&self) -> usize {
+        self.expected_char.width()
+    }
 }
 
 pub enum CharStatus {
@@ -14,25 +35,56 @@ pub enum CharStatus {
     Empty,
 }
 
+/// Scans target-text graphemes for whitespace-delimited word spans, as
+/// start/end (exclusive) index pairs.
+fn compute_word_spans(game_string: &[GameChar]) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    let mut start = None;
+    for (index, c) in game_string.iter().enumerate() {
+        if c.expected_char.chars().all(char::is_whitespace) {
+            if let Some(word_start) = start.take() {
+                spans.push((word_start, index));
+            }
+        } else if start.is_none() {
+            start = Some(index);
+        }
+    }
+    if let Some(word_start) = start {
+        spans.push((word_start, game_string.len()));
+    }
+    spans
+}
+
 #[derive(Clone)]
 pub struct GameString {
     game_string: Vec<GameChar>,
     curr_index: usize,
+    /// Start/end (exclusive) index pairs for each whitespace-delimited word
+    /// in the target text, precomputed once so word-boundary tracking never
+    /// needs to rescan the whole string.
+    word_spans: Vec<(usize, usize)>,
+    /// Whether each word up to `words_completed()` was typed with no
+    /// uncorrected errors, pushed/popped as `curr_index` crosses a word
+    /// boundary so the count stays correct without rescanning.
+    completed_words: Vec<bool>,
 }
 
 impl GameString {
     pub fn from(s: String) -> GameString {
         let mut res = Vec::new();
-        for character in s.chars() {
+        for grapheme in s.graphemes(true) {
             res.push(GameChar {
-                expected_char: character,
+                expected_char: grapheme.to_string(),
                 given_char: None,
             });
         }
 
+        let word_spans = compute_word_spans(&res);
         GameString {
             game_string: res,
             curr_index: 0,
+            word_spans,
+            completed_words: Vec::new(),
         }
     }
 
@@ -44,44 +96,83 @@ impl GameString {
         self.curr_index == self.game_string.len()
     }
 
-    pub fn get_usr_given_char(&self, index: usize) -> Option<char> {
-        self.game_string[index].given_char
+    pub fn get_usr_given_char(&self, index: usize) -> Option<&str> {
+        self.game_string[index].given_char.as_deref()
     }
 
-    pub fn get_expected_char(&self, index: usize) -> char {
-        self.game_string[index].expected_char
+    pub fn get_expected_char(&self, index: usize) -> &str {
+        &self.game_string[index].expected_char
     }
 
-    pub fn words_completed(&self) -> i32 {
-        // TODO more efficient if we store this data in the struct and
-        // update dynamically as we go.
-        let mut words = 0;
-        for (index, c) in self.game_string.iter().enumerate() {
-            match c.given_char {
-                Some(_) => {
-                    if index % 5 == 0 {
-                        words += 1;
-                    }
-                }
-                None => {
-                    break;
-                }
+    pub fn display_width_at(&self, index: usize) -> usize {
+        self.game_string[index].display_width()
+    }
+
+    /// Number of whitespace-delimited words the player has fully typed past
+    /// (reached the trailing boundary of), regardless of accuracy.
+    pub fn words_completed(&self) -> usize {
+        self.completed_words.len()
+    }
+
+    /// Of the words `words_completed` counts, how many were typed with no
+    /// uncorrected errors.
+    pub fn correct_words(&self) -> usize {
+        self.completed_words.iter().filter(|&&correct| correct).count()
+    }
+
+    /// Raw/net words per minute from completed word counts: raw counts every
+    /// word reached, net only the ones typed cleanly.
+    pub fn raw_vs_net_wpm(&self, minutes: f32) -> (f32, f32) {
+        if minutes < 1e-6 {
+            return (0.0, 0.0);
+        }
+        (
+            self.words_completed() as f32 / minutes,
+            self.correct_words() as f32 / minutes,
+        )
+    }
+
+    fn is_span_correct(&self, span: (usize, usize)) -> bool {
+        self.game_string[span.0..span.1]
+            .iter()
+            .all(|c| c.given_char.as_deref() == Some(c.expected_char.as_str()))
+    }
+
+    /// Pushes newly-completed words onto `completed_words` as `curr_index`
+    /// advances past their trailing boundary.
+    fn sync_completed_words_forward(&mut self) {
+        while self.completed_words.len() < self.word_spans.len() {
+            let span = self.word_spans[self.completed_words.len()];
+            if self.curr_index < span.1 {
+                break;
             }
+            let correct = self.is_span_correct(span);
+            self.completed_words.push(correct);
         }
+    }
 
-        words
+    /// Pops words back off `completed_words` as `curr_index` retreats before
+    /// their trailing boundary (backspacing into a previously-finished word).
+    fn sync_completed_words_backward(&mut self) {
+        while !self.completed_words.is_empty() {
+            let (_, end) = self.word_spans[self.completed_words.len() - 1];
+            if self.curr_index >= end {
+                break;
+            }
+            self.completed_words.pop();
+        }
+    }
+
+    pub fn chars_typed(&self) -> usize {
+        self.curr_index
     }
 
     pub fn percentage_correct(&self) -> f32 {
         let mut res = 0;
         for i in 0..self.curr_index {
-            match self.game_string[i].given_char {
-                Some(typed) => {
-                    if typed == self.game_string[i].expected_char {
-                        res += 1;
-                    }
-                }
-                None => {}
+            if self.game_string[i].given_char.as_deref() == Some(self.game_string[i].expected_char.as_str())
+            {
+                res += 1;
             }
         }
         let divisor = self.curr_index;
@@ -89,7 +180,7 @@ impl GameString {
             0.0
         } else {
             let fraction = (res as f32) / (divisor as f32);
-            (fraction * 100.0).round() as f32
+            (fraction * 100.0).round()
         }
     }
 
@@ -97,9 +188,9 @@ impl GameString {
         if index >= self.game_string.len() {
             CharStatus::Empty
         } else {
-            match self.game_string[index].given_char {
+            match &self.game_string[index].given_char {
                 Some(given) => {
-                    if given == self.game_string[index].expected_char {
+                    if given.as_str() == self.game_string[index].expected_char.as_str() {
                         CharStatus::Correct
                     } else {
                         CharStatus::Incorrect
@@ -110,76 +201,352 @@ impl GameString {
         }
     }
 
-    pub fn update_next_char(&mut self, new_char: char) {
-        if self.curr_index < self.game_string.len() {
-            self.game_string[self.curr_index].given_char = Some(new_char);
-            self.curr_index += 1;
+    /// Feeds one typed keystroke into the current grapheme position. A
+    /// multi-codepoint grapheme (e.g. a base letter plus a combining mark)
+    /// needs as many keystrokes as it has `char`s before the cursor advances.
+    ///
+    /// With `StrictStop`, a completed-but-wrong grapheme doesn't advance the
+    /// cursor: the buffer is cleared and the player must retype it correctly.
+    ///
+    /// Returns whether the cursor actually advanced to the next grapheme, so
+    /// callers that index other per-position data (e.g. keystroke timings)
+    /// by grapheme position know when to record an entry.
+    pub fn update_next_char(&mut self, new_char: char, mods: GameMods) -> bool {
+        if self.curr_index >= self.game_string.len() {
+            return false;
         }
+
+        let current = &mut self.game_string[self.curr_index];
+        let expected_chars = current.expected_char.chars().count();
+        let buf = current.given_char.get_or_insert_with(String::new);
+        buf.push(new_char);
+
+        if buf.chars().count() >= expected_chars {
+            let correct = buf.as_str() == current.expected_char.as_str();
+            if correct || !mods.contains(GameMods::STRICT_STOP) {
+                self.curr_index += 1;
+                self.sync_completed_words_forward();
+                return true;
+            }
+            current.given_char = None;
+        }
+        false
     }
 
-    pub fn pop_char(&mut self) {
+    /// Removes the last keystroke from a partially-typed grapheme at the
+    /// cursor; if there is none, steps the cursor back and clears the
+    /// previous grapheme's whole input. Returns whether the cursor
+    /// actually retreated to the previous grapheme, mirroring
+    /// `update_next_char`'s return value.
+    pub fn pop_char(&mut self) -> bool {
+        if self.curr_index < self.game_string.len() {
+            if let Some(buf) = &mut self.game_string[self.curr_index].given_char {
+                if !buf.is_empty() {
+                    buf.pop();
+                    return false;
+                }
+            }
+        }
+
         if self.curr_index > 0 {
             self.curr_index -= 1;
-            self.game_string[self.curr_index].given_char = None
+            self.game_string[self.curr_index].given_char = None;
+            self.sync_completed_words_backward();
+            return true;
         }
+        false
     }
+
+    /// Start/end (exclusive) index pairs for each whitespace-delimited word
+    /// in the target text, precomputed once at construction.
+    pub fn word_spans(&self) -> Vec<(usize, usize)> {
+        self.word_spans.clone()
+    }
+
+    /// The target text of a `word_spans` span.
+    pub fn word_text(&self, span: (usize, usize)) -> String {
+        self.game_string[span.0..span.1]
+            .iter()
+            .map(|c| c.expected_char.as_str())
+            .collect()
+    }
+
+    /// Rebuilds the full target text, e.g. to key a `Replay` by prompt.
+    pub fn target_text(&self) -> String {
+        self.game_string.iter().map(|c| c.expected_char.as_str()).collect()
+    }
+
+    /// Accuracy (0..=100) for a span the player has fully typed; 0 if it
+    /// hasn't been reached yet.
+    pub fn word_accuracy(&self, span: (usize, usize)) -> f32 {
+        let (start, end) = span;
+        if end <= start || end > self.curr_index {
+            return 0.0;
+        }
+        let correct = self.game_string[start..end]
+            .iter()
+            .filter(|c| c.given_char.as_deref() == Some(c.expected_char.as_str()))
+            .count();
+        (correct as f32 / (end - start) as f32) * 100.0
+    }
+}
+
+/// Gross/net WPM and accuracy for a single round, per the standard typing-test
+/// convention: gross counts every keystroke, net subtracts uncorrected errors.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WpmStats {
+    pub gross: f32,
+    pub net: f32,
+    pub accuracy: f32,
 }
 
+/// Peak/average/consistency over a round's instantaneous WPM samples.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SpeedSummary {
+    pub peak_wpm: f32,
+    pub average_wpm: f32,
+    /// Standard deviation of the per-second WPM samples; lower is steadier.
+    pub consistency: f32,
+}
+
+/// How a round ends: typing the whole phrase, or racing a fixed duration.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RoundMode {
+    Phrase,
+    Timed(u64),
+}
+
+impl RoundMode {
+    pub fn from_length(length: PromptLength) -> RoundMode {
+        match length {
+            PromptLength::Timed(secs) => RoundMode::Timed(secs),
+            PromptLength::Words(_) | PromptLength::Chars(_) => RoundMode::Phrase,
+        }
+    }
+}
+
+/// Seconds a `TimeAttack`-modded round runs for when its length selection
+/// didn't already pick a fixed duration.
+const TIME_ATTACK_DEFAULT_SECS: u64 = 60;
+
 // TODO encpasulate internal values
 #[derive(Clone)]
 pub struct Round {
     pub text: GameString,
     pub start_time: Instant,
+    pub mode: RoundMode,
+    pub mods: GameMods,
     end_time: Option<Instant>,
+    /// One timestamp per typed grapheme position, aligned with `text`;
+    /// powers the live speed graph and the end-of-round speed summary.
+    keystroke_times: Vec<Instant>,
+    /// Every key this round has seen, offset from `start_time`, except
+    /// `Char`/`Backspace` keys that didn't move the grapheme cursor (see
+    /// `handle_input`). The timeline a `Replay` is built from once the round
+    /// ends.
+    event_log: Vec<(Duration, Key)>,
+    /// Identifies this round's source/length configuration, so its replay
+    /// can be found again by `Game::start_round` under the same settings.
+    /// See `Game::replay_key`.
+    replay_key: String,
 }
 
 impl Round {
-    pub fn new(target_str: String) -> Self {
+    pub fn new(target_str: String, mode: RoundMode, mods: GameMods, replay_key: String) -> Self {
+        let mode = if mods.contains(GameMods::TIME_ATTACK) {
+            match mode {
+                RoundMode::Timed(_) => mode,
+                RoundMode::Phrase => RoundMode::Timed(TIME_ATTACK_DEFAULT_SECS),
+            }
+        } else {
+            mode
+        };
+
         Round {
             text: GameString::from(target_str),
             start_time: Instant::now(),
+            mode,
+            mods,
             end_time: None,
+            keystroke_times: Vec::new(),
+            event_log: Vec::new(),
+            replay_key,
         }
     }
 
-    pub fn calculate_wpm(&self) -> i32 {
-        let end_time = match self.end_time {
-            Some(et) => et,
-            None => Instant::now(),
-        };
-        let time_diff = end_time.duration_since(self.start_time);
-        let time_diff_mins = time_diff.as_secs_f32() / 60.0;
-        let wpm = (self.text.words_completed() as f32) / time_diff_mins;
+    pub fn calculate_wpm(&self) -> WpmStats {
+        let end_time = self.end_time.unwrap_or_else(Instant::now);
+        let minutes = end_time.duration_since(self.start_time).as_secs_f32() / 60.0;
+
+        if minutes < 1e-6 {
+            return WpmStats::default();
+        }
+
+        let typed = self.text.chars_typed();
+        let uncorrected_errors = (0..typed)
+            .filter(|&i| self.text.get_usr_given_char(i) != Some(self.text.get_expected_char(i)))
+            .count();
+        let gross = (typed as f32 / 5.0) / minutes;
+        let net = (gross - uncorrected_errors as f32 / minutes).max(0.0);
 
-        wpm.round() as i32
+        WpmStats {
+            gross,
+            net,
+            accuracy: self.text.percentage_correct(),
+        }
     }
 
-    // pub fn is_complete(&self) -> bool {
-    //     match self.end_time {
-    //         Some(_) => true,
-    //         None => false,
-    //     }
-    // }
+    /// Seconds remaining in a `Timed` round; `None` for `Phrase` rounds.
+    pub fn seconds_remaining(&self) -> Option<u64> {
+        match self.mode {
+            RoundMode::Phrase => None,
+            RoundMode::Timed(secs) => Some(secs.saturating_sub(self.start_time.elapsed().as_secs())),
+        }
+    }
+
+    /// Instantaneous WPM for each elapsed second of the round, from the
+    /// number of keystrokes that landed in that second.
+    pub fn wpm_timeline(&self) -> Vec<u64> {
+        if self.keystroke_times.is_empty() {
+            return Vec::new();
+        }
+
+        let total_secs = self
+            .keystroke_times
+            .last()
+            .unwrap()
+            .duration_since(self.start_time)
+            .as_secs() as usize
+            + 1;
+        let mut chars_per_second = vec![0u32; total_secs];
+        for t in &self.keystroke_times {
+            let second = t.duration_since(self.start_time).as_secs() as usize;
+            chars_per_second[second] += 1;
+        }
+
+        chars_per_second
+            .into_iter()
+            .map(|chars| ((chars as f32 / 5.0) * 60.0).round() as u64)
+            .collect()
+    }
+
+    pub fn speed_summary(&self) -> SpeedSummary {
+        let samples: Vec<f32> = self.wpm_timeline().into_iter().map(|s| s as f32).collect();
+        if samples.is_empty() {
+            return SpeedSummary::default();
+        }
+
+        let peak_wpm = samples.iter().cloned().fold(0.0_f32, f32::max);
+        let average_wpm = samples.iter().sum::<f32>() / samples.len() as f32;
+        let variance = samples.iter().map(|s| (s - average_wpm).powi(2)).sum::<f32>()
+            / samples.len() as f32;
+
+        SpeedSummary {
+            peak_wpm,
+            average_wpm,
+            consistency: variance.sqrt(),
+        }
+    }
 
-    pub fn handle_input(&mut self, key: &KeyEvent) {
-        match key.code {
-            KeyCode::Char(typed) => {
-                self.text.update_next_char(typed);
+    /// Per-word (word text, accuracy%, wpm) for every word reached so far,
+    /// used to feed per-word attempts into the SM-2 scheduler.
+    pub fn word_performance(&self) -> Vec<(String, f32, f32)> {
+        self.text
+            .word_spans()
+            .into_iter()
+            .take_while(|&(_, end)| end <= self.text.chars_typed())
+            .map(|span| (self.text.word_text(span), self.text.word_accuracy(span), self.word_wpm(span)))
+            .collect()
+    }
+
+    /// WPM for the keystrokes spanning `(start, end)` of the target text.
+    fn word_wpm(&self, span: (usize, usize)) -> f32 {
+        let (start, end) = span;
+        if end == 0 || end > self.keystroke_times.len() {
+            return 0.0;
+        }
+        let span_start = if start == 0 {
+            self.start_time
+        } else {
+            self.keystroke_times[start - 1]
+        };
+        let span_end = self.keystroke_times[end - 1];
+        let minutes = span_end.duration_since(span_start).as_secs_f32() / 60.0;
+        if minutes < 1e-6 {
+            return 0.0;
+        }
+        ((end - start) as f32 / 5.0) / minutes
+    }
+
+    pub fn handle_input(&mut self, key: Key) {
+        // `Char`/`Backspace` are only logged (and timestamped) when they
+        // actually move the grapheme cursor: `event_log` is replayed by
+        // `Replay::cursor_at` on the assumption that each logged one steps
+        // the cursor by exactly one, and `keystroke_times` is indexed by
+        // grapheme position (see `word_wpm`). A keystroke rejected by
+        // `StrictStop`, or one that only partially fills a multi-codepoint
+        // grapheme, doesn't move that cursor, so it must not be logged as if
+        // it did.
+        match key {
+            Key::Char(typed) => {
+                if self.text.update_next_char(typed, self.mods) {
+                    self.log_event(key);
+                    self.keystroke_times.push(Instant::now());
+                }
                 if self.text.is_completed() {
                     self.end_time = Some(Instant::now());
                 }
             }
-            KeyCode::Backspace => {
-                self.text.pop_char();
+            Key::Backspace => {
+                if self.text.pop_char() {
+                    self.log_event(key);
+                    self.keystroke_times.pop();
+                }
             }
-            _ => {}
+            _ => self.log_event(key),
+        }
+        self.finish_if_time_up();
+    }
+
+    fn log_event(&mut self, key: Key) {
+        self.event_log.push((self.start_time.elapsed(), key));
+    }
+
+    /// Exports this round's recorded keystrokes as a `Replay`, so it can be
+    /// saved to disk and raced against later.
+    pub fn to_replay(&self) -> Replay {
+        let events = self
+            .event_log
+            .iter()
+            .map(|&(offset, key)| ReplayEvent { offset, key })
+            .collect();
+        Replay::new(self.replay_key.clone(), self.calculate_wpm().net, events)
+    }
+
+    /// Ends a `Timed` round once its duration has elapsed, scoring whatever
+    /// was completed so far. Called after every keystroke and on idle ticks
+    /// so the round still ends if the player stops typing.
+    pub fn finish_if_time_up(&mut self) {
+        if self.end_time.is_some() {
+            return;
         }
+        if let Some(0) = self.seconds_remaining() {
+            self.end_time = Some(Instant::now());
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.end_time.is_some()
     }
 }
 
 pub enum GameStatus {
     Waiting,
     Ongoing(Round),
+    /// Like `Ongoing`, but racing against a previously recorded `Replay` for
+    /// the same source/length configuration (typically the player's personal
+    /// best under those settings).
+    Racing { round: Round, ghost: Replay },
     Complete,
 }
 
@@ -187,13 +554,25 @@ pub enum GameStatus {
 pub struct Game {
     pub status: GameStatus,
     pub record: Vec<Round>,
+    pub prompt_source: PromptSource,
+    pub prompt_length: PromptLength,
+    pub scheduler: Scheduler,
+    pub mods: GameMods,
+    /// `None` if the local database couldn't be opened; round history is
+    /// still played normally, it just won't be persisted.
+    pub storage: Option<Storage>,
 }
 
 impl Game {
-    pub fn new() -> Self {
+    pub fn with_mods(mods: GameMods) -> Self {
         Game {
             status: GameStatus::Waiting,
             record: Vec::new(),
+            prompt_source: PromptSource::default(),
+            prompt_length: *LENGTH_PRESETS.first().unwrap(),
+            scheduler: Scheduler::new(),
+            mods,
+            storage: Storage::open_default().ok(),
         }
     }
 
@@ -201,56 +580,274 @@ impl Game {
         &self.status
     }
 
+    /// Free functions rather than `&mut self` methods so they can be called
+    /// from inside a `match &mut self.status` arm without fighting the
+    /// borrow checker over an unrelated field.
+    fn cycle_source(current: &PromptSource, step: isize) -> PromptSource {
+        let index = SELECTABLE_SOURCES
+            .iter()
+            .position(|s| s == current)
+            .unwrap_or(0) as isize;
+        let len = SELECTABLE_SOURCES.len() as isize;
+        SELECTABLE_SOURCES[(index + step).rem_euclid(len) as usize].clone()
+    }
+
+    fn cycle_length(current: PromptLength, step: isize) -> PromptLength {
+        let index = LENGTH_PRESETS
+            .iter()
+            .position(|l| l == &current)
+            .unwrap_or(0) as isize;
+        let len = LENGTH_PRESETS.len() as isize;
+        LENGTH_PRESETS[(index + step).rem_euclid(len) as usize]
+    }
+
+    /// Generates the next round's target text from the given source and
+    /// length selection, falling back to a plain lipsum prompt if the
+    /// configured source fails (e.g. a missing corpus file). `Words` prompts
+    /// are steered towards words the scheduler has due for review; other
+    /// length modes generate straight from `source`. `Punctuation`/`Numbers`
+    /// mods then inject extra tokens into the result.
+    fn next_prompt(
+        source: &PromptSource,
+        length: PromptLength,
+        scheduler: &Scheduler,
+        mods: GameMods,
+    ) -> String {
+        let prompt = match length {
+            PromptLength::Words(n) => Game::adaptive_prompt(source, n, scheduler),
+            other => corpus::generate(source, other).unwrap_or_else(|_| lipsum::lipsum(10)),
+        };
+        Game::apply_token_mods(prompt, mods)
+    }
+
+    /// Sprinkles punctuation and/or digit tokens onto word endings when the
+    /// matching mod is active.
+    fn apply_token_mods(prompt: String, mods: GameMods) -> String {
+        if !mods.intersects(GameMods::PUNCTUATION | GameMods::NUMBERS) {
+            return prompt;
+        }
+
+        const PUNCTUATION_MARKS: &[&str] = &[",", ".", "!", "?", ";"];
+        let mut rng = rand::thread_rng();
+
+        prompt
+            .split_whitespace()
+            .map(|word| {
+                let mut word = word.to_string();
+                if mods.contains(GameMods::PUNCTUATION) && rng.gen_bool(0.3) {
+                    word.push_str(PUNCTUATION_MARKS.choose(&mut rng).unwrap());
+                }
+                if mods.contains(GameMods::NUMBERS) && rng.gen_bool(0.2) {
+                    word.push_str(&rng.gen_range(0..10).to_string());
+                }
+                word
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Builds a `Words(n)` prompt mostly out of due/overdue words from the
+    /// scheduler, filling any remaining slots from `source` and shuffling so
+    /// the due words aren't all clumped at the front.
+    fn adaptive_prompt(source: &PromptSource, n: usize, scheduler: &Scheduler) -> String {
+        let due = scheduler.due_words(SystemTime::now());
+        let due_budget = n.saturating_sub(n / 4).max(1).min(n);
+        let mut words: Vec<String> = due.into_iter().take(due_budget).map(str::to_string).collect();
+
+        let filler_needed = n.saturating_sub(words.len());
+        if filler_needed > 0 {
+            if let Ok(filler) = corpus::generate(source, PromptLength::Words(filler_needed)) {
+                words.extend(filler.split_whitespace().map(str::to_string));
+            }
+        }
+
+        if words.is_empty() {
+            return lipsum::lipsum(n);
+        }
+        words.shuffle(&mut rand::thread_rng());
+        words.join(" ")
+    }
+
+    /// Scores and records every word reached in `round` against the SM-2
+    /// scheduler, so future prompts can prioritise the words that need it.
+    fn record_word_attempts(scheduler: &mut Scheduler, round: &Round) {
+        let average_wpm = round.speed_summary().average_wpm;
+        for (word, accuracy, wpm) in round.word_performance() {
+            let quality = Scheduler::quality(accuracy, wpm, average_wpm);
+            scheduler.record_attempt(&word, quality);
+        }
+    }
+
+    /// Logs a completed round to the local database, if one is open.
+    fn persist_round(storage: &Option<Storage>, round: &Round) {
+        if let Some(storage) = storage {
+            let wpm = round.calculate_wpm();
+            let record = RoundRecord::now(round.text.target_text(), wpm.net, wpm.accuracy, round.mods);
+            let _ = storage.insert_round(&record);
+        }
+    }
+
+    /// Identifies a round's replay "slot": prompts are freshly generated
+    /// (every source, `File` included, picks its words at random) so an
+    /// exact text match almost never recurs. Ghosts are instead matched by
+    /// source/length configuration, racing the player against their best
+    /// prior run under the same settings rather than the same literal text.
+    fn replay_key(prompt_source: &PromptSource, prompt_length: PromptLength) -> String {
+        format!("{prompt_source:?}|{prompt_length:?}")
+    }
+
+    /// Builds the next round's `GameStatus`, racing the player against the
+    /// fastest prior replay recorded under the same source/length settings,
+    /// if one is on disk.
+    fn start_round(
+        prompt_source: &PromptSource,
+        prompt_length: PromptLength,
+        scheduler: &Scheduler,
+        mods: GameMods,
+    ) -> GameStatus {
+        let prompt = Game::next_prompt(prompt_source, prompt_length, scheduler, mods);
+        let mode = RoundMode::from_length(prompt_length);
+        let key = Game::replay_key(prompt_source, prompt_length);
+        match replay::fastest_for(&key) {
+            Some(ghost) => GameStatus::Racing {
+                round: Round::new(prompt, mode, mods, key),
+                ghost,
+            },
+            None => GameStatus::Ongoing(Round::new(prompt, mode, mods, key)),
+        }
+    }
+
     pub fn elapsed_time(&self) -> Duration {
         match &self.status {
             GameStatus::Waiting => Duration::ZERO,
             GameStatus::Ongoing(round) => round.start_time.elapsed(),
+            GameStatus::Racing { round, .. } => round.start_time.elapsed(),
             GameStatus::Complete => Duration::ZERO,
         }
     }
 
-    pub fn handle_input(&mut self, key: KeyEvent) -> bool {
+    pub fn handle_input(&mut self, key: Key) -> bool {
         // Check for exit
-        match key.code {
-            KeyCode::Esc => return true,
-            _ => {}
+        if key == Key::Esc {
+            return true;
         }
 
         // Handle controls through the round state.
         let mut finished_round = None;
         match &mut self.status {
-            GameStatus::Waiting => {
-                // Enter the letter given and start the game
-                match key.code {
-                    KeyCode::Enter => {
-                        self.status = GameStatus::Ongoing(Round::new(lipsum(5)));
-                    }
-                    _ => {}
+            GameStatus::Waiting => match key {
+                // Let the player pick source/length before starting the round.
+                Key::Left => {
+                    self.prompt_source = Game::cycle_source(&self.prompt_source, -1)
                 }
-            }
+                Key::Right => {
+                    self.prompt_source = Game::cycle_source(&self.prompt_source, 1)
+                }
+                Key::Up => self.prompt_length = Game::cycle_length(self.prompt_length, 1),
+                Key::Down => self.prompt_length = Game::cycle_length(self.prompt_length, -1),
+                Key::Enter => {
+                    self.status =
+                        Game::start_round(&self.prompt_source, self.prompt_length, &self.scheduler, self.mods);
+                }
+                _ => {}
+            },
             GameStatus::Ongoing(round) => {
-                round.handle_input(&key);
-                if round.end_time.is_some() {
+                round.handle_input(key);
+                if round.is_complete() {
                     finished_round = Some(round.clone());
                 }
             }
-            GameStatus::Complete => match key.code {
-                KeyCode::Enter => {
-                    self.status = GameStatus::Ongoing(Round::new(lipsum(10)));
+            GameStatus::Racing { round, .. } => {
+                round.handle_input(key);
+                if round.is_complete() {
+                    finished_round = Some(round.clone());
                 }
-                _ => {}
-            },
+            }
+            GameStatus::Complete => {
+                if key == Key::Enter {
+                    self.status =
+                        Game::start_round(&self.prompt_source, self.prompt_length, &self.scheduler, self.mods);
+                }
+            }
         }
 
         // Update GameState if necessary
-        match finished_round {
-            Some(round) => {
+        if let Some(round) = finished_round {
+            Game::record_word_attempts(&mut self.scheduler, &round);
+            Game::persist_round(&self.storage, &round);
+            let _ = replay::store(&round.to_replay());
+            self.status = GameStatus::Complete;
+            self.record.push(round);
+        }
+
+        false
+    }
+
+    /// Called on idle ticks (no keystroke arrived) so a `Timed` round still
+    /// ends once its clock runs out, even if the player stops typing.
+    pub fn tick(&mut self) {
+        let round = match &mut self.status {
+            GameStatus::Ongoing(round) => Some(round),
+            GameStatus::Racing { round, .. } => Some(round),
+            _ => None,
+        };
+
+        if let Some(round) = round {
+            round.finish_if_time_up();
+            if round.is_complete() {
+                let round = round.clone();
+                Game::record_word_attempts(&mut self.scheduler, &round);
+                Game::persist_round(&self.storage, &round);
+                let _ = replay::store(&round.to_replay());
                 self.status = GameStatus::Complete;
                 self.record.push(round);
             }
-            None => {}
         }
+    }
+}
 
-        false
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn type_str(text: &mut GameString, typed: &str) {
+        for ch in typed.chars() {
+            text.update_next_char(ch, GameMods::empty());
+        }
+    }
+
+    #[test]
+    fn raw_vs_net_wpm_counts_every_completed_word_as_raw_and_only_clean_ones_as_net() {
+        let mut text = GameString::from("cat dog".to_string());
+        type_str(&mut text, "cag dog");
+
+        assert_eq!(text.words_completed(), 2);
+        assert_eq!(text.correct_words(), 1);
+        assert_eq!(text.raw_vs_net_wpm(1.0), (2.0, 1.0));
+    }
+
+    #[test]
+    fn raw_vs_net_wpm_is_zero_with_no_elapsed_time() {
+        let text = GameString::from("cat dog".to_string());
+        assert_eq!(text.raw_vs_net_wpm(0.0), (0.0, 0.0));
+    }
+
+    #[test]
+    fn calculate_wpm_reports_full_accuracy_with_no_uncorrected_errors() {
+        let mut round = Round::new(
+            "hi there".to_string(),
+            RoundMode::Phrase,
+            GameMods::empty(),
+            "test".to_string(),
+        );
+        std::thread::sleep(Duration::from_millis(20));
+        for ch in "hi there".chars() {
+            round.handle_input(Key::Char(ch));
+        }
+
+        let wpm = round.calculate_wpm();
+        assert_eq!(wpm.accuracy, 100.0);
+        assert_eq!(wpm.net, wpm.gross);
+        assert!(wpm.gross > 0.0);
     }
 }
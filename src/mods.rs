@@ -0,0 +1,59 @@
+//! Composable gameplay modifiers, inspired by osu!-style bitflag mods: each
+//! flag independently changes how a prompt is generated, scored, or
+//! rendered. `Game`/`Round` consult these rather than branching on a pile of
+//! separate booleans.
+
+use bitflags::bitflags;
+
+bitflags! {
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub struct GameMods: u8 {
+        /// Hide already-typed characters instead of echoing them back.
+        const HIDDEN = 0b0000_0001;
+        /// Inject punctuation tokens into the generated text.
+        const PUNCTUATION = 0b0000_0010;
+        /// Inject digit tokens into the generated text.
+        const NUMBERS = 0b0000_0100;
+        /// A wrong keystroke blocks progress until corrected, rather than
+        /// advancing past it.
+        const STRICT_STOP = 0b0000_1000;
+        /// Round ends at a fixed duration, scoring whatever was completed.
+        const TIME_ATTACK = 0b0001_0000;
+    }
+}
+
+/// Short token used for each mod in the `"hd+punc"`-style string form.
+const MOD_TOKENS: &[(GameMods, &str)] = &[
+    (GameMods::HIDDEN, "hd"),
+    (GameMods::PUNCTUATION, "punc"),
+    (GameMods::NUMBERS, "num"),
+    (GameMods::STRICT_STOP, "strict"),
+    (GameMods::TIME_ATTACK, "ta"),
+];
+
+impl GameMods {
+    /// Parses a `+`-separated token string like `"hd+punc"` into the
+    /// matching flags. Unknown tokens are silently ignored, so a typo just
+    /// drops that one mod rather than failing the whole parse.
+    pub fn parse(s: &str) -> GameMods {
+        let mut mods = GameMods::empty();
+        for token in s.split('+') {
+            let token = token.trim();
+            if let Some((flag, _)) = MOD_TOKENS.iter().find(|(_, name)| *name == token) {
+                mods |= *flag;
+            }
+        }
+        mods
+    }
+
+    /// Renders the active mods back to `+`-separated token form, in
+    /// `MOD_TOKENS` order, so the result round-trips through `parse`.
+    pub fn to_token_string(self) -> String {
+        MOD_TOKENS
+            .iter()
+            .filter(|(flag, _)| self.contains(*flag))
+            .map(|(_, name)| *name)
+            .collect::<Vec<_>>()
+            .join("+")
+    }
+}
@@ -0,0 +1,243 @@
+//! Recorded keystroke timelines ("replays") that can be serialized to disk
+//! and played back as a ghost opponent in a later round.
+
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::backend::Key;
+
+/// One keystroke and how far into the round it landed.
+#[derive(Clone, Copy, Debug)]
+pub struct ReplayEvent {
+    pub offset: Duration,
+    pub key: Key,
+}
+
+/// A recorded run under a specific round configuration (see
+/// `Game::replay_key`): every keystroke paired with its offset from the
+/// start of the round.
+#[derive(Clone, Debug)]
+pub struct Replay {
+    pub key: String,
+    pub wpm: f32,
+    pub events: Vec<ReplayEvent>,
+}
+
+impl Replay {
+    pub fn new(key: String, wpm: f32, events: Vec<ReplayEvent>) -> Self {
+        Replay { key, wpm, events }
+    }
+
+    /// The text cursor position the ghost had reached `elapsed` into the
+    /// round: binary-searches the event offsets for the events at or before
+    /// `elapsed`, then replays those events to count the cursor's steps.
+    pub fn cursor_at(&self, elapsed: Duration) -> usize {
+        let slot = self.events.partition_point(|event| event.offset <= elapsed);
+        let mut index: usize = 0;
+        for event in &self.events[..slot] {
+            match event.key {
+                Key::Char(_) => index += 1,
+                Key::Backspace => index = index.saturating_sub(1),
+                _ => {}
+            }
+        }
+        index
+    }
+
+    /// Serializes the replay as plain text: config key line, wpm line, then
+    /// one `offset_millis<TAB>key` line per event. Hand-rolled rather than
+    /// pulling in a serialization crate for something this small.
+    fn save(&self, path: &Path) -> io::Result<()> {
+        let mut out = format!("{}\n{}\n", self.key, self.wpm);
+        for event in &self.events {
+            out.push_str(&format!(
+                "{}\t{}\n",
+                event.offset.as_millis(),
+                encode_key(event.key)
+            ));
+        }
+        fs::write(path, out)
+    }
+
+    fn load(path: &Path) -> io::Result<Replay> {
+        let contents = fs::read_to_string(path)?;
+        let mut lines = contents.lines();
+        let key = lines.next().unwrap_or_default().to_string();
+        let wpm = lines.next().and_then(|line| line.parse().ok()).unwrap_or(0.0);
+
+        let mut events = Vec::new();
+        for line in lines {
+            let mut parts = line.splitn(2, '\t');
+            let offset_ms: u64 = match parts.next().and_then(|p| p.parse().ok()) {
+                Some(value) => value,
+                None => continue,
+            };
+            let key = match parts.next() {
+                Some(encoded) => decode_key(encoded),
+                None => continue,
+            };
+            events.push(ReplayEvent {
+                offset: Duration::from_millis(offset_ms),
+                key,
+            });
+        }
+
+        Ok(Replay { key, wpm, events })
+    }
+}
+
+fn encode_key(key: Key) -> String {
+    match key {
+        Key::Char(c) => format!("c{c}"),
+        Key::Backspace => "b".to_string(),
+        Key::Enter => "e".to_string(),
+        Key::Esc => "x".to_string(),
+        Key::Left => "l".to_string(),
+        Key::Right => "r".to_string(),
+        Key::Up => "u".to_string(),
+        Key::Down => "d".to_string(),
+        Key::Other => "o".to_string(),
+    }
+}
+
+fn decode_key(encoded: &str) -> Key {
+    let mut chars = encoded.chars();
+    match chars.next() {
+        Some('c') => Key::Char(chars.next().unwrap_or(' ')),
+        Some('b') => Key::Backspace,
+        Some('e') => Key::Enter,
+        Some('x') => Key::Esc,
+        Some('l') => Key::Left,
+        Some('r') => Key::Right,
+        Some('u') => Key::Up,
+        Some('d') => Key::Down,
+        _ => Key::Other,
+    }
+}
+
+/// Directory replays are stored under, relative to the directory the game is
+/// run from.
+const REPLAY_DIR: &str = ".terminal_typer_replays";
+
+fn path_for(key: &str, wpm: f32) -> PathBuf {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    Path::new(REPLAY_DIR).join(format!("{:x}-{:.0}.replay", hasher.finish(), wpm))
+}
+
+/// Persists `replay` under `REPLAY_DIR`, creating the directory if needed.
+pub fn store(replay: &Replay) -> io::Result<()> {
+    fs::create_dir_all(REPLAY_DIR)?;
+    replay.save(&path_for(&replay.key, replay.wpm))
+}
+
+/// Finds the highest-WPM replay previously recorded under the same
+/// source/length configuration `key` (see `Game::replay_key`), if any.
+pub fn fastest_for(key: &str) -> Option<Replay> {
+    let entries = fs::read_dir(REPLAY_DIR).ok()?;
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| Replay::load(&entry.path()).ok())
+        .filter(|replay| replay.key == key)
+        .max_by(|a, b| a.wpm.partial_cmp(&b.wpm).unwrap_or(std::cmp::Ordering::Equal))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(offset_ms: u64, key: Key) -> ReplayEvent {
+        ReplayEvent {
+            offset: Duration::from_millis(offset_ms),
+            key,
+        }
+    }
+
+    #[test]
+    fn encode_decode_round_trips_every_key_variant() {
+        let keys = [
+            Key::Char('a'),
+            Key::Char('☃'),
+            Key::Backspace,
+            Key::Enter,
+            Key::Esc,
+            Key::Left,
+            Key::Right,
+            Key::Up,
+            Key::Down,
+            Key::Other,
+        ];
+
+        for key in keys {
+            assert_eq!(decode_key(&encode_key(key)), key);
+        }
+    }
+
+    #[test]
+    fn save_load_round_trips_key_wpm_and_events() {
+        let dir = std::env::temp_dir().join(format!(
+            "terminal_typer_replay_test_{:x}",
+            {
+                let mut hasher = DefaultHasher::new();
+                "save_load_round_trips_key_wpm_and_events".hash(&mut hasher);
+                hasher.finish()
+            }
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("test.replay");
+
+        let replay = Replay::new(
+            "corpus|40".to_string(),
+            72.5,
+            vec![event(0, Key::Char('h')), event(120, Key::Backspace), event(250, Key::Enter)],
+        );
+        replay.save(&path).unwrap();
+
+        let loaded = Replay::load(&path).unwrap();
+        assert_eq!(loaded.key, replay.key);
+        assert_eq!(loaded.wpm, replay.wpm);
+        assert_eq!(loaded.events.len(), replay.events.len());
+        for (a, b) in loaded.events.iter().zip(replay.events.iter()) {
+            assert_eq!(a.offset, b.offset);
+            assert_eq!(a.key, b.key);
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn cursor_at_tracks_char_and_backspace_events_only() {
+        let replay = Replay::new(
+            "corpus|40".to_string(),
+            0.0,
+            vec![
+                event(0, Key::Char('h')),
+                event(10, Key::Char('i')),
+                event(20, Key::Backspace),
+                event(30, Key::Enter),
+                event(40, Key::Char('!')),
+            ],
+        );
+
+        assert_eq!(replay.cursor_at(Duration::from_millis(5)), 1);
+        assert_eq!(replay.cursor_at(Duration::from_millis(15)), 2);
+        assert_eq!(replay.cursor_at(Duration::from_millis(25)), 1);
+        assert_eq!(replay.cursor_at(Duration::from_millis(35)), 1);
+        assert_eq!(replay.cursor_at(Duration::from_millis(45)), 2);
+    }
+
+    #[test]
+    fn cursor_at_never_goes_below_zero() {
+        let replay = Replay::new(
+            "corpus|40".to_string(),
+            0.0,
+            vec![event(0, Key::Backspace), event(10, Key::Backspace)],
+        );
+
+        assert_eq!(replay.cursor_at(Duration::from_millis(20)), 0);
+    }
+}
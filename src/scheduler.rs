@@ -0,0 +1,164 @@
+//! Tracks per-word typing performance and schedules words for review using
+//! the SM-2 spaced-repetition algorithm, so practice rounds can be steered
+//! towards words the player actually struggles with.
+
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+
+/// One word's SM-2 scheduling state.
+#[derive(Clone, Copy, Debug)]
+pub struct WordRecord {
+    pub easiness: f32,
+    pub repetitions: u32,
+    pub interval_days: f32,
+    pub due_at: SystemTime,
+}
+
+impl Default for WordRecord {
+    fn default() -> Self {
+        WordRecord {
+            easiness: 2.5,
+            repetitions: 0,
+            interval_days: 0.0,
+            due_at: SystemTime::now(),
+        }
+    }
+}
+
+impl WordRecord {
+    /// Applies one SM-2 review of quality `q` (0..=5).
+    fn review(&mut self, quality: u8) {
+        let q = quality.min(5) as f32;
+
+        if q < 3.0 {
+            self.repetitions = 0;
+            self.interval_days = 1.0;
+        } else {
+            self.repetitions += 1;
+            self.interval_days = match self.repetitions {
+                1 => 1.0,
+                2 => 6.0,
+                _ => self.interval_days * self.easiness,
+            };
+        }
+
+        self.easiness =
+            (self.easiness + 0.1 - (5.0 - q) * (0.08 + (5.0 - q) * 0.02)).max(1.3);
+        self.due_at = SystemTime::now() + Duration::from_secs_f32(self.interval_days * 86400.0);
+    }
+}
+
+/// Per-word SM-2 state for every word the player has typed before.
+#[derive(Default)]
+pub struct Scheduler {
+    words: HashMap<String, WordRecord>,
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        Scheduler::default()
+    }
+
+    /// Quality score (0..=5) from a word's accuracy and its WPM relative to
+    /// the round's average: any mistake caps it below the "remembered"
+    /// threshold of 3, clean words score higher the faster they were typed.
+    pub fn quality(accuracy: f32, wpm: f32, round_average_wpm: f32) -> u8 {
+        if accuracy < 100.0 {
+            return if accuracy >= 50.0 { 2 } else { 0 };
+        }
+        if round_average_wpm <= 0.0 {
+            return 4;
+        }
+        let relative = wpm / round_average_wpm;
+        if relative >= 1.1 {
+            5
+        } else if relative >= 0.9 {
+            4
+        } else {
+            3
+        }
+    }
+
+    /// Records one typing attempt at `word` and applies the SM-2 update.
+    pub fn record_attempt(&mut self, word: &str, quality: u8) {
+        self.words.entry(word.to_lowercase()).or_default().review(quality);
+    }
+
+    /// Words due or overdue for review, most-overdue first.
+    pub fn due_words(&self, now: SystemTime) -> Vec<&str> {
+        let mut due: Vec<(&str, SystemTime)> = self
+            .words
+            .iter()
+            .filter(|(_, record)| record.due_at <= now)
+            .map(|(word, record)| (word.as_str(), record.due_at))
+            .collect();
+        due.sort_by_key(|(_, due_at)| *due_at);
+        due.into_iter().map(|(word, _)| word).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn review_below_threshold_resets_repetitions_and_interval() {
+        let mut record = WordRecord {
+            repetitions: 4,
+            interval_days: 30.0,
+            ..WordRecord::default()
+        };
+
+        record.review(2);
+
+        assert_eq!(record.repetitions, 0);
+        assert_eq!(record.interval_days, 1.0);
+    }
+
+    #[test]
+    fn review_at_or_above_threshold_follows_sm2_interval_progression() {
+        let mut record = WordRecord::default();
+
+        record.review(5);
+        assert_eq!(record.repetitions, 1);
+        assert_eq!(record.interval_days, 1.0);
+
+        record.review(5);
+        assert_eq!(record.repetitions, 2);
+        assert_eq!(record.interval_days, 6.0);
+
+        let easiness_before_third = record.easiness;
+        record.review(5);
+        assert_eq!(record.repetitions, 3);
+        assert_eq!(record.interval_days, 6.0 * easiness_before_third);
+    }
+
+    #[test]
+    fn easiness_never_drops_below_the_sm2_floor() {
+        let mut record = WordRecord::default();
+        for _ in 0..20 {
+            record.review(0);
+        }
+        assert!(record.easiness >= 1.3);
+    }
+
+    #[test]
+    fn quality_caps_below_three_on_any_mistake() {
+        assert_eq!(Scheduler::quality(100.0, 80.0, 40.0), 5);
+        assert_eq!(Scheduler::quality(80.0, 80.0, 40.0), 2);
+        assert_eq!(Scheduler::quality(30.0, 80.0, 40.0), 0);
+    }
+
+    #[test]
+    fn due_words_are_sorted_most_overdue_first() {
+        let mut scheduler = Scheduler::new();
+        scheduler.record_attempt("slow", 5);
+        scheduler.words.get_mut("slow").unwrap().due_at = SystemTime::UNIX_EPOCH;
+        scheduler.record_attempt("fast", 5);
+        scheduler.words.get_mut("fast").unwrap().due_at =
+            SystemTime::UNIX_EPOCH + Duration::from_secs(1);
+
+        let due = scheduler.due_words(SystemTime::UNIX_EPOCH + Duration::from_secs(100));
+        assert_eq!(due, vec!["slow", "fast"]);
+    }
+}
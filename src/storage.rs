@@ -0,0 +1,147 @@
+//! Persists completed rounds to a local SQLite database in the user's data
+//! directory, so progress survives across runs. Schema changes go through
+//! versioned migrations rather than editing tables in place.
+
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rusqlite::{params, Connection};
+
+use crate::mods::GameMods;
+
+/// Schema migrations, applied in order starting from whatever
+/// `schema_version` currently holds. Each entry runs exactly once, ever.
+const MIGRATIONS: &[&str] = &[
+    "CREATE TABLE rounds (
+        id INTEGER PRIMARY KEY,
+        target TEXT NOT NULL,
+        wpm REAL NOT NULL,
+        accuracy REAL NOT NULL,
+        mods INTEGER NOT NULL,
+        completed_at INTEGER NOT NULL
+    )",
+];
+
+/// One completed round as stored in (or read back from) the database.
+/// `completed_at` is a Unix timestamp, seconds since the epoch.
+pub struct RoundRecord {
+    pub target: String,
+    pub wpm: f32,
+    pub accuracy: f32,
+    pub mods: GameMods,
+    pub completed_at: i64,
+}
+
+impl RoundRecord {
+    pub fn now(target: String, wpm: f32, accuracy: f32, mods: GameMods) -> Self {
+        let completed_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        RoundRecord { target, wpm, accuracy, mods, completed_at }
+    }
+}
+
+pub struct Storage {
+    conn: Connection,
+}
+
+impl Storage {
+    /// Opens (creating if needed) the database at its default path under the
+    /// user's data directory, running any pending migrations.
+    pub fn open_default() -> rusqlite::Result<Storage> {
+        Storage::open(&default_path())
+    }
+
+    pub fn open(path: &Path) -> rusqlite::Result<Storage> {
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        let conn = Connection::open(path)?;
+        Storage::migrate(&conn)?;
+        Ok(Storage { conn })
+    }
+
+    fn migrate(conn: &Connection) -> rusqlite::Result<()> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS schema_version (version INTEGER NOT NULL)",
+        )?;
+        let applied: i64 = conn.query_row(
+            "SELECT COALESCE(MAX(version), 0) FROM schema_version",
+            [],
+            |row| row.get(0),
+        )?;
+
+        for (index, migration) in MIGRATIONS.iter().enumerate() {
+            let version = index as i64 + 1;
+            if version <= applied {
+                continue;
+            }
+            conn.execute_batch(migration)?;
+            conn.execute("INSERT INTO schema_version (version) VALUES (?1)", params![version])?;
+        }
+
+        Ok(())
+    }
+
+    /// Records one completed round.
+    pub fn insert_round(&self, record: &RoundRecord) -> rusqlite::Result<()> {
+        self.conn.execute(
+            "INSERT INTO rounds (target, wpm, accuracy, mods, completed_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                record.target,
+                record.wpm,
+                record.accuracy,
+                record.mods.bits(),
+                record.completed_at
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// The highest WPM recorded across all rounds, if any have been logged.
+    /// Not wired into the UI yet; reserved for an upcoming stats screen.
+    #[allow(dead_code)]
+    pub fn best_wpm(&self) -> rusqlite::Result<Option<f32>> {
+        self.conn.query_row("SELECT MAX(wpm) FROM rounds", [], |row| row.get(0))
+    }
+
+    /// Average accuracy over the most recently completed `last_n` rounds.
+    /// Not wired into the UI yet; reserved for an upcoming stats screen.
+    #[allow(dead_code)]
+    pub fn rolling_average_accuracy(&self, last_n: u32) -> rusqlite::Result<Option<f32>> {
+        self.conn.query_row(
+            "SELECT AVG(accuracy) FROM (SELECT accuracy FROM rounds ORDER BY id DESC LIMIT ?1)",
+            params![last_n],
+            |row| row.get(0),
+        )
+    }
+
+    /// Every round completed at or after `since` (a Unix timestamp), oldest
+    /// first, so the TUI can plot a trend line. Not wired into the UI yet;
+    /// reserved for an upcoming stats screen.
+    #[allow(dead_code)]
+    pub fn history_since(&self, since: i64) -> rusqlite::Result<Vec<RoundRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT target, wpm, accuracy, mods, completed_at FROM rounds \
+             WHERE completed_at >= ?1 ORDER BY completed_at",
+        )?;
+        let rows = stmt.query_map(params![since], |row| {
+            Ok(RoundRecord {
+                target: row.get(0)?,
+                wpm: row.get(1)?,
+                accuracy: row.get(2)?,
+                mods: GameMods::from_bits_truncate(row.get(3)?),
+                completed_at: row.get(4)?,
+            })
+        })?;
+        rows.collect()
+    }
+}
+
+fn default_path() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("terminal_typer")
+        .join("history.sqlite3")
+}
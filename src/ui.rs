@@ -3,13 +3,154 @@ use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout},
     style::Style,
     text::{Line, Span},
-    widgets::{Block, Borders, Paragraph, Wrap},
+    widgets::{Block, Borders, Paragraph, Sparkline, Wrap},
     Frame,
 };
+use unicode_width::UnicodeWidthStr;
 
-use crate::game::{CharStatus, Game, GameStatus};
+use crate::corpus::PromptLength;
+use crate::game::{CharStatus, Game, GameStatus, Round, RoundMode};
+use crate::mods::GameMods;
+use crate::replay::Replay;
 
-pub fn draw<B: Backend>(f: &mut Frame<B>, game: &mut Game) {
+fn length_label(length: PromptLength) -> String {
+    match length {
+        PromptLength::Words(n) => format!("{n} words"),
+        PromptLength::Chars(n) => format!("{n} chars"),
+        PromptLength::Timed(secs) => format!("{secs}s timed"),
+    }
+}
+
+/// Right-pads `s` with spaces so it occupies `width` terminal columns,
+/// keeping the prompt aligned when a typed substitute is narrower than the
+/// glyph it stands in for.
+fn pad_to_width(s: &str, width: usize) -> String {
+    let actual = s.width();
+    if actual < width {
+        format!("{}{}", s, " ".repeat(width - actual))
+    } else {
+        s.to_string()
+    }
+}
+
+/// Renders the prompt text, coloured by correctness, with the ghost's
+/// current cursor (if racing) underlined so it's visible alongside the
+/// player's own progress.
+fn render_prompt(round: &Round, ghost_index: Option<usize>) -> Vec<Span<'static>> {
+    let mut rendered_text = Vec::new();
+    for i in 0..round.text.len() {
+        let mut style = match round.text.status_at_index(i) {
+            CharStatus::Correct => Style::default().fg(ratatui::style::Color::Green),
+            CharStatus::Incorrect => Style::default().fg(ratatui::style::Color::Red),
+            CharStatus::Empty => Style::default().fg(ratatui::style::Color::Gray),
+        };
+        if ghost_index == Some(i) {
+            style = style.add_modifier(ratatui::style::Modifier::UNDERLINED);
+        }
+
+        // TODO abstract this functionality into the class. Fine here for now.
+        let rendered_grapheme = match round.text.get_usr_given_char(i) {
+            Some(_) if round.mods.contains(GameMods::HIDDEN) => "\u{25cf}".to_string(),
+            Some(given) => {
+                if given != " " {
+                    given.to_string()
+                } else {
+                    "·".to_string()
+                }
+            }
+            None => round.text.get_expected_char(i).to_string(),
+        };
+        // Pad narrower substitutes out to the expected glyph's column width
+        // so wide graphemes (CJK, emoji) don't shift the rest of the prompt
+        // out of alignment.
+        let padded = pad_to_width(&rendered_grapheme, round.text.display_width_at(i));
+        rendered_text.push(Span::styled(padded, style));
+    }
+    rendered_text
+}
+
+/// Margin around the prompt paragraph in a full-screen layout. Inline mode
+/// uses `INLINE_PROMPT_MARGIN` instead: `INLINE_VIEWPORT_HEIGHT` (see
+/// `main.rs`) is far too short for this one, and would collapse the prompt's
+/// rect to zero height.
+const PROMPT_MARGIN: u16 = 10;
+const INLINE_PROMPT_MARGIN: u16 = 1;
+
+/// Shared layout for the live typing screen, used by both a plain `Ongoing`
+/// round and a `Racing` round (which additionally shows a ghost cursor and
+/// label).
+fn draw_round<B: Backend>(
+    f: &mut Frame<B>,
+    chunks: &[ratatui::layout::Rect],
+    game: &Game,
+    round: &Round,
+    ghost: Option<&Replay>,
+    inline: bool,
+) {
+    let ghost_index = ghost.map(|g| g.cursor_at(round.start_time.elapsed()));
+    let rendered_text = render_prompt(round, ghost_index);
+
+    let prompt_area = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(70), Constraint::Percentage(30)].as_ref())
+        .split(chunks[0]);
+    let prompt_margin = if inline { INLINE_PROMPT_MARGIN } else { PROMPT_MARGIN };
+    let prompt_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(prompt_margin)
+        .constraints([Constraint::Percentage(80)].as_ref())
+        .split(prompt_area[0]);
+    let prompt_title = if ghost.is_some() { " Prompt (racing a ghost) " } else { " Prompt " };
+    let prompt_block = Block::default()
+        .title(prompt_title)
+        .borders(Borders::ALL)
+        .style(Style::default().fg(ratatui::style::Color::White).bg(ratatui::style::Color::Black));
+    f.render_widget(prompt_block, prompt_area[0]);
+    let prompt_para = Paragraph::new(Line::from(rendered_text))
+        .style(
+            Style::default()
+                .fg(ratatui::style::Color::White)
+                .bg(ratatui::style::Color::Black),
+        )
+        .wrap(Wrap { trim: true })
+        .alignment(Alignment::Center);
+    f.render_widget(prompt_para, prompt_layout[0]);
+
+    // Live speed graph: instantaneous WPM per elapsed second.
+    let timeline = round.wpm_timeline();
+    let speed_graph = Sparkline::default()
+        .block(Block::default().title(" Speed (WPM) ").borders(Borders::ALL))
+        .style(Style::default().fg(ratatui::style::Color::Cyan))
+        .data(&timeline);
+    f.render_widget(speed_graph, prompt_area[1]);
+
+    let mut stats = format!(
+        "Word Accuracy: {}% \t \t Time Elapsed: {}.{}",
+        round.text.percentage_correct(),
+        game.elapsed_time().as_secs(),
+        game.elapsed_time().subsec_millis()
+    );
+    if let RoundMode::Timed(_) = round.mode {
+        if let Some(remaining) = round.seconds_remaining() {
+            stats.push_str(&format!(" \t \t Time Left: {remaining}s"));
+        }
+    }
+    if let Some(ghost) = ghost {
+        stats.push_str(&format!(" \t \t Ghost: {:.0} wpm", ghost.wpm));
+    }
+
+    let block2 = Paragraph::new(stats)
+        .block(
+            Block::default()
+                .title("Stats")
+                .borders(Borders::ALL)
+                .style(Style::default().bg(ratatui::style::Color::Black)),
+        )
+        .style(Style::default());
+    f.render_widget(block2, chunks[1]);
+}
+
+pub fn draw<B: Backend>(f: &mut Frame<B>, game: &mut Game, inline: bool) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .margin(1)
@@ -50,6 +191,13 @@ pub fn draw<B: Backend>(f: &mut Frame<B>, game: &mut Game) {
                 .alignment(Alignment::Center);
             f.render_widget(title_box, chunks[0]);
 
+            let mods_label = game.mods.to_token_string();
+            let selection = format!(
+                "Source: {}   Length: {}   Mods: {}",
+                game.prompt_source.label(),
+                length_label(game.prompt_length),
+                if mods_label.is_empty() { "none" } else { &mods_label }
+            );
             let prompt_msg = vec![
                 Line::from(Span::styled(
                     "[Enter]: New Game",
@@ -59,6 +207,11 @@ pub fn draw<B: Backend>(f: &mut Frame<B>, game: &mut Game) {
                     "  [Esc]: Exit Game",
                     Style::default().fg(ratatui::style::Color::Yellow),
                 )),
+                Line::from(Span::styled(
+                    "  [\u{2190}\u{2192}]: Source   [\u{2191}\u{2193}]: Length",
+                    Style::default().fg(ratatui::style::Color::Yellow),
+                )),
+                Line::from(Span::raw(selection)),
             ];
             let prompt_box = Paragraph::new(prompt_msg)
                 .block(Block::default().title(" Controls ").borders(Borders::ALL))
@@ -70,68 +223,18 @@ pub fn draw<B: Backend>(f: &mut Frame<B>, game: &mut Game) {
             f.render_widget(prompt_box, chunks[1]);
         }
         GameStatus::Ongoing(round) => {
-            let mut rendered_text = Vec::new();
-            for i in 0..round.text.len() {
-                // Assign style for character status
-                let style = match round.text.status_at_index(i) {
-                    CharStatus::Correct => Style::default().fg(ratatui::style::Color::Green),
-                    CharStatus::Incorrect => Style::default().fg(ratatui::style::Color::Red),
-                    CharStatus::Empty => Style::default().fg(ratatui::style::Color::Gray),
-                };
-
-                // TODO abstract this functionality into the class. Fine here for now.
-                let rendered_char = match round.text.get_usr_given_char(i) {
-                    Some(c) => {
-                        if c != ' ' {
-                            c
-                        } else {
-                            '·'
-                        }
-                    }
-                    None => round.text.get_expected_char(i),
-                };
-                rendered_text.push(Span::styled(rendered_char.to_string(), style));
-            }
-
-            let prompt_layout = Layout::default()
-                .direction(Direction::Vertical)
-                .margin(10)
-                .constraints([Constraint::Percentage(80)].as_ref())
-                .split(chunks[0]);
-            let prompt_block = Block::default().title(" Prompt ").borders(Borders::ALL).style(Style::default().fg(ratatui::style::Color::White).bg(ratatui::style::Color::Black));
-            f.render_widget(prompt_block, chunks[0]);
-            let prompt_para = Paragraph::new(Line::from(rendered_text))
-                .style(
-                    Style::default()
-                        .fg(ratatui::style::Color::White)
-                        .bg(ratatui::style::Color::Black),
-                )
-                .wrap(Wrap { trim: true })
-                .alignment(Alignment::Center);
-            f.render_widget(prompt_para, prompt_layout[0]);
-
-            let accuracy = format!(
-                "Word Accuracy: {}% \t \t Time Elapsed: {}.{}",
-                round.text.percentage_correct(),
-                game.elapsed_time().as_secs(),
-                game.elapsed_time().subsec_millis()
-            );
-
-            let block2 = Paragraph::new(accuracy)
-                .block(
-                    Block::default()
-                        .title("Stats")
-                        .borders(Borders::ALL)
-                        .style(Style::default().bg(ratatui::style::Color::Black)),
-                )
-                .style(Style::default());
-            f.render_widget(block2, chunks[1]);
+            draw_round(f, &chunks, game, round, None, inline);
+        }
+        GameStatus::Racing { round, ghost } => {
+            draw_round(f, &chunks, game, round, Some(ghost), inline);
         }
         GameStatus::Complete => {
             let waiting_msg = "Previous rounds:";
             let mut lines = vec![Line::from(Span::raw(waiting_msg))];
             for (index, round) in game.record.iter().enumerate() {
                 let curr_count = index + 1;
+                let wpm = round.calculate_wpm();
+                let speed = round.speed_summary();
                 let new_line = Line::from(vec![
                     Span::styled("Round ", Style::default().fg(ratatui::style::Color::Green)),
                     Span::styled(
@@ -139,10 +242,17 @@ pub fn draw<B: Backend>(f: &mut Frame<B>, game: &mut Game) {
                         Style::default().fg(ratatui::style::Color::Green),
                     ),
                     Span::raw(": "),
-                    Span::raw(round.text.percentage_correct().to_string()),
+                    Span::raw(format!("{}", wpm.accuracy)),
                     Span::raw("% word accuracy, "),
-                    Span::raw(round.calculate_wpm().to_string()),
-                    Span::raw(" wpm"),
+                    Span::raw(format!("{:.0}", wpm.net)),
+                    Span::raw(" net wpm ("),
+                    Span::raw(format!("{:.0}", wpm.gross)),
+                    Span::raw(" gross), peak "),
+                    Span::raw(format!("{:.0}", speed.peak_wpm)),
+                    Span::raw(", avg "),
+                    Span::raw(format!("{:.0}", speed.average_wpm)),
+                    Span::raw(", consistency \u{00b1}"),
+                    Span::raw(format!("{:.0}", speed.consistency)),
                 ]);
                 lines.push(new_line);
             }